@@ -0,0 +1,202 @@
+//! IO throughput benchmarks: sequential write and random read, compared
+//! across syscall strategies selectable via `--io-mode`.
+
+use std::alloc::{alloc, Layout};
+use std::fs::{self, File, OpenOptions};
+use std::io::{IoSlice, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::sync::Arc;
+
+use clap::ValueEnum;
+use rand::prelude::*;
+use rayon::prelude::*;
+
+use crate::results::{BenchRecord, RunResults};
+use crate::stats::measure;
+use crate::Args;
+
+#[cfg(target_os = "linux")]
+use std::os::unix::fs::OpenOptionsExt;
+#[cfg(target_os = "macos")]
+use std::os::unix::io::AsRawFd;
+
+const IO_FILE_SIZE: usize = 4 * 1024 * 1024 * 1024;
+const IO_READ_ITERATIONS: u32 = 20_000;
+const DIRECT_BLOCK_SIZE: usize = 4096;
+
+/// Syscall strategy used by the IO benchmark.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum IoMode {
+    /// `seek` + `read_exact` on a per-thread file handle (the original path).
+    Seek,
+    /// Random read via `read_at` (pread) on a file handle shared by all threads,
+    /// so positioned reads don't serialize on a per-fd cursor. The sequential
+    /// write in this mode is the same single-buffer `write_all` loop as `Seek`;
+    /// no benchmark uses `write_at`.
+    Positioned,
+    /// Sequential write via `write_vectored` over an array of `IoSlice` buffers,
+    /// to compare with the single-buffer `write_all` loop that the other modes
+    /// run. The random read in this mode is the `Seek` one.
+    Vectored,
+}
+
+pub fn io_benchmarks(
+    path: &Path,
+    threads: usize,
+    args: &Args,
+    mode: IoMode,
+    results: &mut RunResults,
+) -> (f64, f64) {
+    println!("\n[IO Performance ({:?})]", mode);
+    let file_path = path.join("bench_large.bin");
+    let size = IO_FILE_SIZE;
+
+    let seq_write_gbps = match mode {
+        IoMode::Vectored => bench_vectored_write(&file_path, size, args, results),
+        IoMode::Seek | IoMode::Positioned => bench_sequential_write(&file_path, size, args, results),
+    };
+
+    let rand_read_gbps = match mode {
+        IoMode::Positioned => bench_positioned_read(&file_path, threads, size, args, results),
+        IoMode::Seek | IoMode::Vectored => bench_seek_read(&file_path, threads, size, args, results),
+    };
+
+    let _ = fs::remove_file(file_path);
+
+    (seq_write_gbps, rand_read_gbps)
+}
+
+fn bench_sequential_write(file_path: &Path, size: usize, args: &Args, results: &mut RunResults) -> f64 {
+    let stats = measure("  Sequential Write (Mono)", args.heavy_warmup, args.heavy_samples, args.min_time(), || {
+        let mut f = File::create(file_path).unwrap();
+        let buf = vec![0u8; 1024 * 64];
+        for _ in 0..(size / buf.len()) {
+            f.write_all(&buf).unwrap();
+        }
+    });
+    let gbps = size as f64 / stats.mean.as_secs_f64() / 1e9;
+    results.push(BenchRecord::new("io_seq_write", "io_seq_write", 1, &stats, Some(gbps)));
+    gbps
+}
+
+fn bench_vectored_write(file_path: &Path, size: usize, args: &Args, results: &mut RunResults) -> f64 {
+    const BUFS_PER_CALL: usize = 4;
+    const BUF_SIZE: usize = 16 * 1024;
+    let call_size = BUFS_PER_CALL * BUF_SIZE;
+
+    let stats = measure("  Sequential Write Vectored (Mono)", args.heavy_warmup, args.heavy_samples, args.min_time(), || {
+        let mut f = File::create(file_path).unwrap();
+        let flat = vec![0u8; call_size];
+
+        for _ in 0..(size / call_size) {
+            // `write_vectored` is allowed to return a short count just like
+            // `write`, so loop (rebuilding `IoSlice`s over whatever's left,
+            // still split into BUF_SIZE chunks) instead of asserting one shot
+            // always finishes the call.
+            let mut written = 0;
+            while written < call_size {
+                let slices: Vec<IoSlice> = flat[written..].chunks(BUF_SIZE).map(IoSlice::new).collect();
+                let n = f.write_vectored(&slices).unwrap();
+                assert_ne!(n, 0, "write_vectored returned 0 with data remaining");
+                written += n;
+            }
+        }
+    });
+    let gbps = size as f64 / stats.mean.as_secs_f64() / 1e9;
+    results.push(BenchRecord::new("io_seq_write", "io_seq_write", 1, &stats, Some(gbps)));
+    gbps
+}
+
+fn bench_seek_read(file_path: &Path, threads: usize, size: usize, args: &Args, results: &mut RunResults) -> f64 {
+    // Open each thread's handle once, up front, the same as bench_positioned_read's
+    // shared handle — otherwise every timed sample here would also pay a fresh
+    // open()/O_DIRECT setup per thread, biasing `--io-mode seek` vs `--io-mode
+    // positioned` comparisons.
+    let mut handles: Vec<File> = (0..threads).map(|_| open_with_direct_io(file_path).unwrap()).collect();
+
+    let stats = measure("  Random Read Seek (Multi)", args.heavy_warmup, args.heavy_samples, args.min_time(), || {
+        handles.par_iter_mut().for_each(|f| {
+            let mut rng = rand::rng();
+            let valid_positions = size / DIRECT_BLOCK_SIZE;
+
+            unsafe {
+                let layout = Layout::from_size_align(DIRECT_BLOCK_SIZE, DIRECT_BLOCK_SIZE).unwrap();
+                let ptr = alloc(layout);
+                let buf = std::slice::from_raw_parts_mut(ptr, DIRECT_BLOCK_SIZE);
+
+                for _ in 0..IO_READ_ITERATIONS {
+                    let pos = rng.random_range(0..valid_positions);
+                    f.seek(SeekFrom::Start((pos * DIRECT_BLOCK_SIZE) as u64)).unwrap();
+                    f.read_exact(buf).unwrap();
+                }
+            }
+        });
+    });
+    let gbps = (threads * IO_READ_ITERATIONS as usize * DIRECT_BLOCK_SIZE) as f64 / stats.mean.as_secs_f64() / 1e9;
+    results.push(BenchRecord::new("io_rand_read", "io_rand_read", threads, &stats, Some(gbps)));
+    gbps
+}
+
+fn bench_positioned_read(file_path: &Path, threads: usize, size: usize, args: &Args, results: &mut RunResults) -> f64 {
+    // All worker threads share one fd and issue pread at independent offsets,
+    // instead of each holding (and seeking) its own handle.
+    let shared_file = Arc::new(open_with_direct_io(file_path).unwrap());
+
+    let stats = measure("  Random Read Positioned (Multi)", args.heavy_warmup, args.heavy_samples, args.min_time(), || {
+        (0..threads).into_par_iter().for_each(|_| {
+            let f = Arc::clone(&shared_file);
+            let mut rng = rand::rng();
+            let valid_positions = size / DIRECT_BLOCK_SIZE;
+
+            unsafe {
+                let layout = Layout::from_size_align(DIRECT_BLOCK_SIZE, DIRECT_BLOCK_SIZE).unwrap();
+                let ptr = alloc(layout);
+                let buf = std::slice::from_raw_parts_mut(ptr, DIRECT_BLOCK_SIZE);
+
+                for _ in 0..IO_READ_ITERATIONS {
+                    let pos = rng.random_range(0..valid_positions);
+                    f.read_at(buf, (pos * DIRECT_BLOCK_SIZE) as u64).unwrap();
+                }
+            }
+        });
+    });
+    let gbps = (threads * IO_READ_ITERATIONS as usize * DIRECT_BLOCK_SIZE) as f64 / stats.mean.as_secs_f64() / 1e9;
+    results.push(BenchRecord::new("io_rand_read", "io_rand_read", threads, &stats, Some(gbps)));
+    gbps
+}
+
+fn open_with_direct_io(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create(true);
+
+    // --- Linux Logic ---
+    #[cfg(target_os = "linux")]
+    {
+        options.custom_flags(libc::O_DIRECT);
+        options.open(path)
+    }
+
+    // --- macOS Logic ---
+    #[cfg(target_os = "macos")]
+    {
+        let file = options.open(path).unwrap();
+        let fd = file.as_raw_fd();
+
+        unsafe {
+            // F_NOCACHE turns off the page cache for this file descriptor
+            if libc::fcntl(fd, libc::F_NOCACHE, 1) == -1 {
+                println!("fcntl failed");
+                return Err(std::io::Error::last_os_error());
+            } else {
+            }
+        }
+        Ok(file)
+    }
+
+    // --- Fallback for other OSs ---
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        options.open(path)
+    }
+}
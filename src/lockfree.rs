@@ -0,0 +1,155 @@
+//! Lock-free Treiber stack throughput benchmark: a single `AtomicPtr` head
+//! hammered via `compare_exchange_weak`, contrasted with the mutex and
+//! array-atomic contention tests.
+//!
+//! This is a throughput micro-benchmark, not a production-ready stack: the
+//! classic Treiber design is vulnerable to the ABA problem (a thread reads
+//! `head`, gets preempted, another thread pops that node and pushes back a
+//! different node at the same freed address, and the first thread's CAS then
+//! succeeds against a head that's logically not the one it observed). Real
+//! implementations need hazard pointers or epoch-based reclamation to close
+//! this; we accept the risk here since the benchmark only cares about raw
+//! CAS throughput, and every run uses bounded per-thread iteration counts so
+//! the stack can't grow without limit.
+
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crate::results::{BenchRecord, RunResults};
+use crate::stats::measure;
+use crate::Args;
+
+const LOCKFREE_ITERATIONS: u32 = 2_000_000;
+
+struct Node {
+    value: u64,
+    next: *mut Node,
+}
+
+/// A Treiber stack: push and pop both race on a single `AtomicPtr` head via
+/// a `compare_exchange_weak` CAS-retry loop, with no other synchronization.
+struct TreiberStack {
+    head: AtomicPtr<Node>,
+}
+
+impl TreiberStack {
+    fn new() -> Self {
+        TreiberStack { head: AtomicPtr::new(std::ptr::null_mut()) }
+    }
+
+    /// Returns the number of CAS retries taken.
+    fn push(&self, value: u64) -> usize {
+        let node = Box::into_raw(Box::new(Node { value, next: std::ptr::null_mut() }));
+        let mut retries = 0;
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            unsafe { (*node).next = head };
+            match self.head.compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return retries,
+                Err(_) => retries += 1,
+            }
+        }
+    }
+
+    /// Returns the popped value (`None` if the stack was empty) and the
+    /// number of CAS retries taken.
+    fn pop(&self) -> (Option<u64>, usize) {
+        let mut retries = 0;
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            if head.is_null() {
+                return (None, retries);
+            }
+            let next = unsafe { (*head).next };
+            match self.head.compare_exchange_weak(head, next, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return (Some(unsafe { Box::from_raw(head) }.value), retries),
+                Err(_) => retries += 1,
+            }
+        }
+    }
+}
+
+impl Drop for TreiberStack {
+    fn drop(&mut self) {
+        while self.pop().0.is_some() {}
+    }
+}
+
+/// Splits `num_threads` into producers (pushing) and consumers (popping)
+/// according to `producer_ratio`, runs them concurrently against one shared
+/// stack, and reports combined push+pop throughput plus CAS-retry counts.
+pub fn lockfree_benchmark(
+    num_threads: usize,
+    producer_ratio: f64,
+    args: &Args,
+    results: &mut RunResults,
+) -> f64 {
+    println!("\n[Lock-free stack (Treiber)]");
+    let num_producers = ((num_threads as f64 * producer_ratio).round() as usize)
+        .clamp(1, num_threads.saturating_sub(1).max(1));
+    println!("  producers={} consumers={}", num_producers, num_threads - num_producers);
+
+    let stack = Arc::new(TreiberStack::new());
+    let push_retries = Arc::new(AtomicUsize::new(0));
+    let pop_retries = Arc::new(AtomicUsize::new(0));
+    let pop_misses = Arc::new(AtomicUsize::new(0));
+
+    let stats = measure("  Treiber push/pop", args.heavy_warmup, args.heavy_samples, args.min_time(), || {
+        push_retries.store(0, Ordering::Relaxed);
+        pop_retries.store(0, Ordering::Relaxed);
+        pop_misses.store(0, Ordering::Relaxed);
+
+        let mut handles = vec![];
+        for t in 0..num_threads {
+            let stack = Arc::clone(&stack);
+            let push_retries = Arc::clone(&push_retries);
+            let pop_retries = Arc::clone(&pop_retries);
+            let pop_misses = Arc::clone(&pop_misses);
+            let is_producer = t < num_producers;
+
+            handles.push(thread::spawn(move || {
+                let mut local_retries = 0usize;
+                let mut local_misses = 0usize;
+
+                for i in 0..LOCKFREE_ITERATIONS {
+                    if is_producer {
+                        local_retries += stack.push(i as u64);
+                    } else {
+                        let (value, retries) = stack.pop();
+                        local_retries += retries;
+                        if value.is_none() {
+                            local_misses += 1;
+                        }
+                    }
+                }
+
+                if is_producer {
+                    push_retries.fetch_add(local_retries, Ordering::Relaxed);
+                } else {
+                    pop_retries.fetch_add(local_retries, Ordering::Relaxed);
+                    pop_misses.fetch_add(local_misses, Ordering::Relaxed);
+                }
+            }));
+        }
+
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        // Drain whatever producers left behind so the next sample starts empty.
+        while stack.pop().0.is_some() {}
+    });
+
+    let ops_per_sec = (num_threads as f64 * LOCKFREE_ITERATIONS as f64) / stats.mean.as_secs_f64();
+    println!(
+        "  ops/sec={:.0} push_retries={} pop_retries={} pop_misses={}",
+        ops_per_sec,
+        push_retries.load(Ordering::Relaxed),
+        pop_retries.load(Ordering::Relaxed),
+        pop_misses.load(Ordering::Relaxed),
+    );
+
+    results.push(BenchRecord::new("lockfree_stack", "lockfree_stack", num_threads, &stats, Some(ops_per_sec)));
+    ops_per_sec
+}
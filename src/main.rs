@@ -1,40 +1,124 @@
-use std::alloc::{alloc, Layout};
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::fs::{self, File, OpenOptions};
-use std::io::{Read, Write, Seek, SeekFrom};
+mod io_bench;
+mod lockfree;
+mod mem_bench;
+mod results;
+mod scoring;
+mod stats;
+
+use std::fs::{self, File};
 use std::path::{Path, PathBuf};
-use std::time::{Instant};
-use rand::prelude::*;
+use std::time::Duration;
 use clap::Parser;
 use rayon::prelude::*;
-use libc;
 use num_cpus;
 
-#[cfg(target_os = "linux")]
-use std::os::unix::fs::OpenOptionsExt;
-#[cfg(target_os = "macos")]
-use std::os::unix::io::AsRawFd;
-
+use results::{BenchRecord, OutputFormat, RunResults};
+use scoring::{CategoryScore, RefProfile, ScoreReport};
+use stats::measure;
+
+/// With the defaults below (20/3 samples/warmup for the cheap CPU and
+/// non-shared memory-bandwidth sections, 3/1 for everything else — shared
+/// memory, atomics, the lock-free stack, IO, and filesystem metadata, which
+/// already move multiple GiB or tens of millions of iterations per sample),
+/// a full run takes roughly 1-3 minutes on typical hardware. Raise
+/// `--samples`/`--heavy-samples` for more statistical confidence, or lower
+/// them for a quick smoke test.
 #[derive(Parser, Debug)]
-struct Args {
+pub struct Args {
     #[arg(short, long)]
-    dir: PathBuf,
+    pub dir: PathBuf,
 
     #[arg(short, long)]
-    threads: Option<usize>,
+    pub threads: Option<usize>,
+
+    /// Number of timing samples to collect per benchmark (cheap sections only;
+    /// see `--heavy-samples` for the IO/filesystem/shared-memory sections)
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..))]
+    pub samples: u32,
+
+    /// Untimed warmup iterations run before sampling starts (cheap sections only)
+    #[arg(long, default_value_t = 3)]
+    pub warmup: u32,
+
+    /// Number of timing samples for sections whose one-shot workload is
+    /// already multi-second/multi-GiB (shared memory, atomics, the lock-free
+    /// stack, IO, filesystem): kept low so sweeping many combinations (e.g.
+    /// `--atomic-ops`) doesn't multiply an already-heavy run by `--samples`
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub heavy_samples: u32,
+
+    /// Untimed warmup iterations before sampling starts, for the same heavy
+    /// sections as `--heavy-samples`
+    #[arg(long, default_value_t = 1)]
+    pub heavy_warmup: u32,
+
+    /// Minimum time (ms) to keep sampling a benchmark, even past `--samples`/`--heavy-samples`
+    #[arg(long, default_value_t = 100)]
+    pub min_time: u64,
+
+    /// Custom reference machine profile (TOML or JSON) to score against
+    #[arg(long)]
+    pub reference: Option<PathBuf>,
+
+    /// Exit non-zero if any category scores below this threshold
+    #[arg(long)]
+    pub require_min_score: Option<f64>,
+
+    /// Syscall strategy for the IO benchmarks (sequential write and random read)
+    #[arg(long, value_enum, default_value = "seek")]
+    pub io_mode: io_bench::IoMode,
+
+    /// Atomic RMW operations to sweep in the contention matrix
+    #[arg(long, value_enum, num_args = 1.., default_values = ["store-load", "fetch-add", "swap", "cas"])]
+    pub atomic_ops: Vec<mem_bench::AtomicOp>,
+
+    /// Lock primitive for the shared-memory access benchmark
+    #[arg(long, value_enum, default_value = "mutex")]
+    pub lock_kind: mem_bench::LockKind,
+
+    /// Shard count for `--lock-kind sharded` (defaults to sweeping 1, threads, 4x threads)
+    #[arg(long, value_parser = parse_positive_usize)]
+    pub shards: Option<usize>,
+
+    /// Write the full run's results to this file, in `--format` (JSON or CSV;
+    /// with `--format text`, the default, no file is written)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// Output format for `--output` (text output is also always printed to stdout)
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Previous run's JSON results to compare this run against
+    #[arg(long)]
+    pub baseline: Option<PathBuf>,
+
+    /// Mean-time regression, in percent, that fails `--baseline` comparison
+    #[arg(long, default_value_t = 5.0)]
+    pub regression_tolerance: f64,
+
+    /// Fraction of threads acting as producers in the lock-free stack benchmark
+    #[arg(long, default_value_t = 0.5)]
+    pub lockfree_producer_ratio: f64,
 }
 
-const CPU_FIBONACCI_IT : u32 = 42;
-
-const SHARED_MEMORY_SIZE : usize = 4 * 1024 * 1024 * 1024;
+impl Args {
+    pub fn min_time(&self) -> Duration {
+        Duration::from_millis(self.min_time)
+    }
+}
 
-const SHARED_MEMORY_ITERATIONS_MUTEX : u32 = 2_000_000;
-const SHARED_MEMORY_ITERATIONS_ATOMIC : u32 = 20_000_000;
+/// `clap::value_parser!(usize).range(..)` isn't available (clap only builds a
+/// ranged parser for the fixed-width integer types), so validate by hand.
+fn parse_positive_usize(s: &str) -> Result<usize, String> {
+    let value: usize = s.parse().map_err(|e| format!("invalid number: {e}"))?;
+    if value < 1 {
+        return Err("value must be at least 1".to_string());
+    }
+    Ok(value)
+}
 
-const IO_FILE_SIZE : usize = 4 * 1024 * 1024 * 1024;
-const IO_READ_ITERATIONS : u32 = 20_000;
+const CPU_FIBONACCI_IT : u32 = 42;
 
 const FS_ITERATIONS: u32 = 50_000;
 
@@ -48,227 +132,126 @@ fn main() {
 
     println!("--- Starting Benchmark (Threads: {}) ---", num_threads);
 
+    let profile = args.reference.as_deref().map(RefProfile::load).unwrap_or_default();
+    let mut report = ScoreReport::new();
+    let mut results = RunResults::new(num_threads);
+
     // 1. CPU Bound: Fibonacci / Heavy Calculation
-    bench_section("CPU Bound (Fibonacci)", || cpu_work(CPU_FIBONACCI_IT), num_threads);
+    let (_, cpu_multi) = bench_section("cpu_fib", || cpu_work(CPU_FIBONACCI_IT), num_threads, &args, &mut results);
+    let cpu_fib_per_sec = num_threads as f64 / cpu_multi.mean.as_secs_f64();
 
     // 2. Memory Bandwidth: Large Array Operations
-    bench_section("Memory Bandwidth (non-shared)", || memory_test_unshared(100_000_000), num_threads);
-
-    memory_test_mutex(num_threads);
-    memory_test_atomic(num_threads);
+    const MEM_ELEMENTS: usize = 100_000_000;
+    let (_, mem_multi) = bench_section("mem_bandwidth", || mem_bench::memory_test_unshared(MEM_ELEMENTS), num_threads, &args, &mut results);
+    let mem_bandwidth_gbps = (num_threads * MEM_ELEMENTS * std::mem::size_of::<f64>()) as f64 / mem_multi.mean.as_secs_f64() / 1e9;
+
+    let mutex_ops_per_sec = match args.lock_kind {
+        mem_bench::LockKind::Mutex => mem_bench::memory_test_mutex(num_threads, &args, &mut results),
+        mem_bench::LockKind::RwLock => mem_bench::memory_test_rwlock(num_threads, &args, &mut results),
+        mem_bench::LockKind::Sharded => mem_bench::memory_test_sharded(num_threads, args.shards, &args, &mut results),
+    };
+    let atomic_ops_per_sec = mem_bench::memory_test_atomic(num_threads, &args.atomic_ops, &args, &mut results);
+    let lockfree_ops_per_sec =
+        lockfree::lockfree_benchmark(num_threads, args.lockfree_producer_ratio, &args, &mut results);
 
     // 3. IO: Sequential and Random
-    io_benchmarks(&args.dir, num_threads);
+    let (io_seq_write_gbps, io_rand_read_gbps) =
+        io_bench::io_benchmarks(&args.dir, num_threads, &args, args.io_mode, &mut results);
 
     // 4. Filesystem: Create/Delete Metadata
-    fs_benchmarks(&args.dir, num_threads);
-}
-
-// --- BENCHMARK LOGIC ---
-
-fn cpu_work(n: u32) {
-    fn fib(n: u32) -> u32 {
-        if n <= 1 { n } else { fib(n - 1) + fib(n - 2) }
+    let fs_ops_per_sec = fs_benchmarks(&args.dir, num_threads, &args, &mut results);
+
+    println!("\n[Score]");
+    report.push(CategoryScore::new("cpu_fib", cpu_fib_per_sec, profile.cpu_fib_per_sec));
+    report.push(CategoryScore::new("mem_bandwidth", mem_bandwidth_gbps, profile.mem_bandwidth_gbps));
+    report.push(CategoryScore::new("mutex_ops", mutex_ops_per_sec, profile.mutex_ops_per_sec));
+    report.push(CategoryScore::new("atomic_ops", atomic_ops_per_sec, profile.atomic_ops_per_sec));
+    report.push(CategoryScore::new("lockfree_ops", lockfree_ops_per_sec, profile.lockfree_ops_per_sec));
+    report.push(CategoryScore::new("io_seq_write", io_seq_write_gbps, profile.io_seq_write_gbps));
+    report.push(CategoryScore::new("io_rand_read", io_rand_read_gbps, profile.io_rand_read_gbps));
+    report.push(CategoryScore::new("fs_ops", fs_ops_per_sec, profile.fs_ops_per_sec));
+    println!("  Aggregate score: {:.1}", report.aggregate());
+
+    if let Some(min_score) = args.require_min_score {
+        let failing = report.below_threshold(min_score);
+        if !failing.is_empty() {
+            eprintln!("\nFAILED: {} categor{} below minimum score {:.1}:", failing.len(), if failing.len() == 1 { "y" } else { "ies" }, min_score);
+            for c in &failing {
+                eprintln!("  {} = {:.1}", c.name, c.score);
+            }
+            std::process::exit(1);
+        }
     }
-    let mut _ret = fib(n);
-}
 
-fn memory_test_unshared(size: usize) {
-    let mut data = vec![1.0f64; size];
-    for i in 0..size {
-        data[i] = data[i] * 2.5 + 1.2;
+    let mut baseline_regressed = false;
+    if let Some(baseline_path) = &args.baseline {
+        let baseline = RunResults::load_json(baseline_path);
+        baseline_regressed = results.compare_to_baseline(&baseline, args.regression_tolerance);
     }
-}
 
-fn memory_test_mutex(num_threads: usize) {
-    println!("\n[Memory shared access (mutex)]");
-    println!("  Allocating");
-    let data = Arc::new(Mutex::new(vec![0u8; SHARED_MEMORY_SIZE]));
-
-    let mut handles = vec![];
-
-    let start = Instant::now();
-
-    println!("  Starting");
-
-    for _t in 0..num_threads {
-        let shared_data = Arc::clone(&data);
-        
-        let handle = thread::spawn(move || {
-            let mut rng = rand::rng();
-            //println!("Thread {} started.", t);
-
-            for _ in 0..SHARED_MEMORY_ITERATIONS_MUTEX {
-                // Randomly pick a block index
-                let idx = rng.random_range(0..SHARED_MEMORY_SIZE - 64);
-                
-                // Lock the mutex to get access
-                let mut mem = shared_data.lock().unwrap();
-                
-                // Randomly Write or Read
-                if rng.random_bool(0.5) {
-                    let random_number: u8 = rng.random();
-                    mem[idx] = random_number;
-                } else {
-                    let _val = mem[idx]; // Read
-                }
-            }
+    if let Some(output_path) = &args.output {
+        results.write(output_path, args.format).unwrap_or_else(|e| {
+            panic!("failed to write results to {}: {}", output_path.display(), e)
         });
-        handles.push(handle);
     }
 
-    for handle in handles {
-        handle.join().unwrap();
+    if baseline_regressed {
+        eprintln!("\nFAILED: one or more benchmarks regressed beyond {:.1}% against baseline", args.regression_tolerance);
+        std::process::exit(1);
     }
-    println!("  Memory shared access (mutex): {:?}", start.elapsed());
 }
 
-fn memory_test_atomic(num_threads: usize) {
-     println!("\n[Memory shared access (atomic)]");
-    let num_elements = SHARED_MEMORY_SIZE / 8; // Since AtomicU64 is 8 bytes
-    
-    println!("  Allocating");
-    let data = Arc::new(unsafe {
-        let layout = std::alloc::Layout::from_size_align(SHARED_MEMORY_SIZE, 4096).unwrap();
-        let ptr = std::alloc::alloc_zeroed(layout) as *mut AtomicU64;
-        Vec::from_raw_parts(ptr, num_elements, num_elements)
-    });
-
-    println!("  Starting");
-
-    let start = Instant::now();
-
-    let mut handles = vec![];
-    for _t in 0..num_threads {
-        let shared_data = Arc::clone(&data);
-        handles.push(thread::spawn(move || {
-            let mut rng = rand::rng();
-            for _ in 0..SHARED_MEMORY_ITERATIONS_ATOMIC {
-                let idx = rng.random_range(0..num_elements);
-                if rng.random_bool(0.5) {
-                    // Relaxed ordering is fastest; doesn't enforce cross-CPU synchronization
-                    shared_data[idx].store(rng.random(), Ordering::Relaxed);
-                } else {
-                    let _val = shared_data[idx].load(Ordering::Relaxed);
-                }
-            }
-        }));
-    }
-
-    for h in handles { h.join().unwrap(); }
-
-    println!("  Memory shared access (atomic): {:?}", start.elapsed());
-}
+// --- BENCHMARK LOGIC ---
 
-fn io_benchmarks(path: &Path, threads: usize) {
-    println!("\n[IO Performance]");
-    let file_path = path.join("bench_large.bin");
-    let size = IO_FILE_SIZE;
-
-    // Mono-thread Sequential Write
-    let start = Instant::now();
-    {
-        let mut f = File::create(&file_path).unwrap();
-        let buf = vec![0u8; 1024 * 64];
-        for _ in 0..(size / buf.len()) {
-            f.write_all(&buf).unwrap();
-        }
+fn cpu_work(n: u32) {
+    fn fib(n: u32) -> u32 {
+        if n <= 1 { n } else { fib(n - 1) + fib(n - 2) }
     }
-    println!("  Sequential Write (Mono): {:?}", start.elapsed());
-
-    // Multi-threaded Random Read
-    let start = Instant::now();
-    (0..threads).into_par_iter().for_each(|_| {
-        const DIRECT_BLOCK_SIZE : usize = 4096;
-
-        let mut f = open_with_direct_io(&file_path).unwrap();
-        let mut rng = rand::rng();
-
-        let valid_positions : usize = size / DIRECT_BLOCK_SIZE;
-
-        unsafe {
-            let layout = Layout::from_size_align(DIRECT_BLOCK_SIZE, DIRECT_BLOCK_SIZE).unwrap();
-            let ptr = alloc(layout);
-            let mut buf = std::slice::from_raw_parts_mut(ptr, DIRECT_BLOCK_SIZE);
-
-            for _it in 0..IO_READ_ITERATIONS {
-                let pos = rng.random_range(0..valid_positions as usize);
-                f.seek(SeekFrom::Start((pos * DIRECT_BLOCK_SIZE) as u64)).unwrap();
-                f.read_exact(&mut buf).unwrap();
-            }
-        }
-    });
-    println!("  Random Read Direct (Multi): {:?}", start.elapsed());
-    
-    let _ = fs::remove_file(file_path);
+    let mut _ret = fib(n);
 }
 
-fn fs_benchmarks(path: &Path, threads: usize) {
+fn fs_benchmarks(path: &Path, threads: usize, args: &Args, results: &mut RunResults) -> f64 {
     println!("\n[Filesystem Metadata]");
     let base = path.join("fs_test");
     fs::create_dir_all(&base).unwrap();
 
-    let start = Instant::now();
-    (0..threads).into_par_iter().for_each(|t| {
-        let thread_dir = base.join(format!("t_{}", t));
-        fs::create_dir(&thread_dir).unwrap();
-        for i in 0..FS_ITERATIONS {
-            let f_path = thread_dir.join(format!("{}.txt", i));
-            File::create(&f_path).unwrap();
-            fs::remove_file(&f_path).unwrap();
-        }
+    let stats = measure("  Create/Delete files/thread", args.heavy_warmup, args.heavy_samples, args.min_time(), || {
+        (0..threads).into_par_iter().for_each(|t| {
+            let thread_dir = base.join(format!("t_{}", t));
+            fs::create_dir(&thread_dir).unwrap();
+            for i in 0..FS_ITERATIONS {
+                let f_path = thread_dir.join(format!("{}.txt", i));
+                File::create(&f_path).unwrap();
+                fs::remove_file(&f_path).unwrap();
+            }
+            fs::remove_dir(&thread_dir).unwrap();
+        });
     });
-    println!("  Create/Delete files/thread: {:?}", start.elapsed());
+
     let _ = fs::remove_dir_all(base);
+
+    let ops_per_sec = (threads as f64 * FS_ITERATIONS as f64) / stats.mean.as_secs_f64();
+    results.push(BenchRecord::new("fs_ops", "fs_ops", threads, &stats, Some(ops_per_sec)));
+    ops_per_sec
 }
 
 // --- UTILS ---
 
-fn bench_section<F>(name: &str, f: F, threads: usize) 
+fn bench_section<F>(
+    name: &str,
+    f: F,
+    threads: usize,
+    args: &Args,
+    results: &mut RunResults,
+) -> (stats::SampleStats, stats::SampleStats)
 where F: Fn() + Sync + Send + Copy {
     println!("\n[{}]", name);
-    
-    let start = Instant::now();
-    f();
-    println!("  Mono-thread:  {:?}", start.elapsed());
-
-    let start = Instant::now();
-    (0..threads).into_par_iter().for_each(|_| f());
-    println!("  Multi-thread: {:?}", start.elapsed());
-}
-
-
-fn open_with_direct_io(path: &PathBuf) -> std::io::Result<File> {
-    let mut options = OpenOptions::new();
-    options.read(true).write(true).create(true);
 
-    // --- Linux Logic ---
-    #[cfg(target_os = "linux")]
-    {
-        options.custom_flags(libc::O_DIRECT);
-        options.open(path)
-    }
-
-    // --- macOS Logic ---
-    #[cfg(target_os = "macos")]
-    {
-        let file = options.open(path).unwrap();
-        let fd = file.as_raw_fd();
-        
-        unsafe {
-            // F_NOCACHE turns off the page cache for this file descriptor
-            if libc::fcntl(fd, libc::F_NOCACHE, 1) == -1 {
-                println!("fcntl failed");
-                return Err(std::io::Error::last_os_error());
-            } else {
-            }
-        }
-        Ok(file)
-    }
-
-    // --- Fallback for other OSs ---
-    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
-    {
-        options.open(path)
-    }
+    let mono = measure("  Mono-thread", args.warmup, args.samples, args.min_time(), f);
+    let multi = measure("  Multi-thread", args.warmup, args.samples, args.min_time(), || {
+        (0..threads).into_par_iter().for_each(|_| f());
+    });
+    results.push(BenchRecord::new(&format!("{}/mono", name), name, 1, &mono, None));
+    results.push(BenchRecord::new(&format!("{}/multi", name), name, threads, &multi, None));
+    (mono, multi)
 }
@@ -0,0 +1,369 @@
+//! Shared-memory contention benchmarks: unshared bandwidth, a single global
+//! mutex (or sharded locks), and an atomic contention matrix sweeping RMW ops
+//! against ordering regimes.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+
+use clap::ValueEnum;
+use rand::prelude::*;
+
+use crate::results::{BenchRecord, RunResults};
+use crate::stats::measure;
+use crate::Args;
+
+const SHARED_MEMORY_SIZE: usize = 4 * 1024 * 1024 * 1024;
+const SHARED_MEMORY_ITERATIONS_MUTEX: u32 = 2_000_000;
+/// Total per-thread iterations budgeted across the *whole* atomic sweep,
+/// regardless of how many `(op, ordering)` combinations are selected. Each
+/// combination gets `SHARED_MEMORY_ITERATIONS_ATOMIC_BUDGET / combos` (but
+/// never fewer than `SHARED_MEMORY_ITERATIONS_ATOMIC_MIN`), so sweeping all 4
+/// ops (12 combinations) doesn't cost 12x a single-combination run.
+const SHARED_MEMORY_ITERATIONS_ATOMIC_BUDGET: u32 = 20_000_000;
+const SHARED_MEMORY_ITERATIONS_ATOMIC_MIN: u32 = 500_000;
+
+pub fn memory_test_unshared(size: usize) {
+    let mut data = vec![1.0f64; size];
+    for i in 0..size {
+        data[i] = data[i] * 2.5 + 1.2;
+    }
+}
+
+pub fn memory_test_mutex(num_threads: usize, args: &Args, results: &mut RunResults) -> f64 {
+    println!("\n[Memory shared access (mutex)]");
+    println!("  Allocating");
+    let data = Arc::new(Mutex::new(vec![0u8; SHARED_MEMORY_SIZE]));
+
+    let stats = measure("  Memory shared access (mutex)", args.heavy_warmup, args.heavy_samples, args.min_time(), || {
+        let mut handles = vec![];
+        for _t in 0..num_threads {
+            let shared_data = Arc::clone(&data);
+
+            let handle = thread::spawn(move || {
+                let mut rng = rand::rng();
+
+                for _ in 0..SHARED_MEMORY_ITERATIONS_MUTEX {
+                    // Randomly pick a block index
+                    let idx = rng.random_range(0..SHARED_MEMORY_SIZE - 64);
+
+                    // Lock the mutex to get access
+                    let mut mem = shared_data.lock().unwrap();
+
+                    // Randomly Write or Read
+                    if rng.random_bool(0.5) {
+                        let random_number: u8 = rng.random();
+                        mem[idx] = random_number;
+                    } else {
+                        let _val = mem[idx]; // Read
+                    }
+                }
+            });
+            handles.push(handle);
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    });
+
+    let ops_per_sec = (num_threads as f64 * SHARED_MEMORY_ITERATIONS_MUTEX as f64) / stats.mean.as_secs_f64();
+    results.push(BenchRecord::new("mutex_ops", "mutex_ops", num_threads, &stats, Some(ops_per_sec)));
+    ops_per_sec
+}
+
+/// Lock primitive used by the shared-memory access benchmark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LockKind {
+    /// A single `Arc<Mutex<Vec<u8>>>` guarding the whole region (the original test).
+    Mutex,
+    /// A single `Arc<RwLock<Vec<u8>>>`, taking a read guard for the 50% read case.
+    RwLock,
+    /// The region split into K independently-locked `Mutex` shards.
+    Sharded,
+}
+
+/// Contrasts the single global mutex with an `RwLock` that separates the
+/// read and write paths, using the same access pattern and iteration count.
+pub fn memory_test_rwlock(num_threads: usize, args: &Args, results: &mut RunResults) -> f64 {
+    println!("\n[Memory shared access (rwlock)]");
+    println!("  Allocating");
+    let data = Arc::new(RwLock::new(vec![0u8; SHARED_MEMORY_SIZE]));
+
+    let stats = measure("  Memory shared access (rwlock)", args.heavy_warmup, args.heavy_samples, args.min_time(), || {
+        let mut handles = vec![];
+        for _t in 0..num_threads {
+            let shared_data = Arc::clone(&data);
+
+            let handle = thread::spawn(move || {
+                let mut rng = rand::rng();
+
+                for _ in 0..SHARED_MEMORY_ITERATIONS_MUTEX {
+                    let idx = rng.random_range(0..SHARED_MEMORY_SIZE - 64);
+
+                    if rng.random_bool(0.5) {
+                        let mut mem = shared_data.write().unwrap();
+                        let random_number: u8 = rng.random();
+                        mem[idx] = random_number;
+                    } else {
+                        let mem = shared_data.read().unwrap();
+                        let _val = mem[idx];
+                    }
+                }
+            });
+            handles.push(handle);
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    });
+
+    let ops_per_sec = (num_threads as f64 * SHARED_MEMORY_ITERATIONS_MUTEX as f64) / stats.mean.as_secs_f64();
+    results.push(BenchRecord::new("mutex_ops", "mutex_ops", num_threads, &stats, Some(ops_per_sec)));
+    ops_per_sec
+}
+
+/// Splits the shared region into K independently-locked shards and routes
+/// each access to a uniformly random shard (then a random offset inside it),
+/// so threads mostly touch disjoint locks.
+/// Sweeps K = 1, `num_threads`, and 4x `num_threads` (or just `shards_override`,
+/// if given) and prints how throughput scales with shard count.
+pub fn memory_test_sharded(
+    num_threads: usize,
+    shards_override: Option<usize>,
+    args: &Args,
+    results: &mut RunResults,
+) -> f64 {
+    println!("\n[Memory shared access (sharded mutex)]");
+    let shard_counts: Vec<usize> = match shards_override {
+        Some(k) => vec![k],
+        None => vec![1, num_threads, 4 * num_threads],
+    };
+
+    println!("  {:<10} {:>16}", "shards", "ops/sec");
+
+    // Each shard needs more than 64 bytes so `0..shard_size - 64` below is a
+    // non-empty range.
+    let max_shards = SHARED_MEMORY_SIZE / 65;
+
+    let mut first_rate = None;
+    for k in shard_counts {
+        assert!(
+            k >= 1 && k <= max_shards,
+            "--shards must be between 1 and {} for a {}-byte region, got {}",
+            max_shards, SHARED_MEMORY_SIZE, k
+        );
+        let shard_size = SHARED_MEMORY_SIZE / k;
+        println!("  Allocating ({} shard{})", k, if k == 1 { "" } else { "s" });
+        let shards: Arc<Vec<Mutex<Vec<u8>>>> = Arc::new((0..k).map(|_| Mutex::new(vec![0u8; shard_size])).collect());
+
+        let label = format!("  shards={}", k);
+        let stats = measure(&label, args.heavy_warmup, args.heavy_samples, args.min_time(), || {
+            let mut handles = vec![];
+            for _t in 0..num_threads {
+                let shards = Arc::clone(&shards);
+
+                handles.push(thread::spawn(move || {
+                    let mut rng = rand::rng();
+
+                    for _ in 0..SHARED_MEMORY_ITERATIONS_MUTEX {
+                        let shard = rng.random_range(0..k);
+                        let idx = rng.random_range(0..shard_size - 64);
+                        let mut mem = shards[shard].lock().unwrap();
+
+                        if rng.random_bool(0.5) {
+                            let random_number: u8 = rng.random();
+                            mem[idx] = random_number;
+                        } else {
+                            let _val = mem[idx];
+                        }
+                    }
+                }));
+            }
+
+            for h in handles {
+                h.join().unwrap();
+            }
+        });
+
+        let ops_per_sec = (num_threads as f64 * SHARED_MEMORY_ITERATIONS_MUTEX as f64) / stats.mean.as_secs_f64();
+        println!("  {:<10} {:>16.0}", k, ops_per_sec);
+        results.push(BenchRecord::new(&format!("mutex_ops/shards={}", k), "mutex_ops", num_threads, &stats, Some(ops_per_sec)));
+        first_rate.get_or_insert(ops_per_sec);
+    }
+
+    first_rate.unwrap_or(0.0)
+}
+
+/// RMW operation exercised by the atomic contention matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AtomicOp {
+    StoreLoad,
+    FetchAdd,
+    Swap,
+    Cas,
+}
+
+/// Memory-ordering regime applied to every operation in the matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OrderingRegime {
+    Relaxed,
+    AcqRel,
+    SeqCst,
+}
+
+impl OrderingRegime {
+    const ALL: [OrderingRegime; 3] = [OrderingRegime::Relaxed, OrderingRegime::AcqRel, OrderingRegime::SeqCst];
+
+    fn load(self) -> Ordering {
+        match self {
+            OrderingRegime::Relaxed => Ordering::Relaxed,
+            OrderingRegime::AcqRel => Ordering::Acquire,
+            OrderingRegime::SeqCst => Ordering::SeqCst,
+        }
+    }
+
+    fn store(self) -> Ordering {
+        match self {
+            OrderingRegime::Relaxed => Ordering::Relaxed,
+            OrderingRegime::AcqRel => Ordering::Release,
+            OrderingRegime::SeqCst => Ordering::SeqCst,
+        }
+    }
+
+    fn rmw(self) -> Ordering {
+        match self {
+            OrderingRegime::Relaxed => Ordering::Relaxed,
+            OrderingRegime::AcqRel => Ordering::AcqRel,
+            OrderingRegime::SeqCst => Ordering::SeqCst,
+        }
+    }
+
+    /// Ordering used on a failed `compare_exchange_weak`, which may not be
+    /// stronger than the success ordering's read component.
+    fn rmw_failure(self) -> Ordering {
+        match self {
+            OrderingRegime::Relaxed => Ordering::Relaxed,
+            OrderingRegime::AcqRel => Ordering::Acquire,
+            OrderingRegime::SeqCst => Ordering::SeqCst,
+        }
+    }
+}
+
+/// Runs every `(op, ordering)` combination across `ops` and prints a table of
+/// throughput and, for `Cas`, CAS-retry counts. Returns the `StoreLoad`/`Relaxed`
+/// throughput (or the first combination run, if that one was excluded) for scoring.
+/// Per-combination iteration count is scaled down as more combinations are
+/// selected (see `SHARED_MEMORY_ITERATIONS_ATOMIC_BUDGET`), so sweeping all 4
+/// ops doesn't cost 12x a single-combination run.
+pub fn memory_test_atomic(num_threads: usize, ops: &[AtomicOp], args: &Args, results: &mut RunResults) -> f64 {
+    println!("\n[Memory shared access (atomic)]");
+    let num_elements = SHARED_MEMORY_SIZE / 8; // Since AtomicU64 is 8 bytes
+
+    println!("  Allocating");
+    let data = Arc::new(unsafe {
+        let layout = std::alloc::Layout::from_size_align(SHARED_MEMORY_SIZE, 4096).unwrap();
+        let ptr = std::alloc::alloc_zeroed(layout) as *mut AtomicU64;
+        Vec::from_raw_parts(ptr, num_elements, num_elements)
+    });
+
+    let combos = ops.len() * OrderingRegime::ALL.len();
+    let iterations_per_combo =
+        (SHARED_MEMORY_ITERATIONS_ATOMIC_BUDGET / combos as u32).max(SHARED_MEMORY_ITERATIONS_ATOMIC_MIN);
+    println!(
+        "  {} combination(s) selected, {} iterations/thread each",
+        combos, iterations_per_combo
+    );
+    println!("  {:<12} {:<8} {:>16} {:>14}", "op", "ordering", "ops/sec", "cas retries");
+
+    let mut first_rate = None;
+    let mut baseline_rate = None;
+
+    for &op in ops {
+        for &regime in &OrderingRegime::ALL {
+            let retries = Arc::new(AtomicUsize::new(0));
+            let label = format!("  {:?}/{:?}", op, regime);
+
+            let stats = measure(&label, args.heavy_warmup, args.heavy_samples, args.min_time(), || {
+                retries.store(0, Ordering::Relaxed);
+                let mut handles = vec![];
+                for _t in 0..num_threads {
+                    let shared_data = Arc::clone(&data);
+                    let retries = Arc::clone(&retries);
+                    handles.push(thread::spawn(move || {
+                        let mut rng = rand::rng();
+                        let mut local_retries = 0usize;
+
+                        for _ in 0..iterations_per_combo {
+                            let idx = rng.random_range(0..num_elements);
+                            match op {
+                                AtomicOp::StoreLoad => {
+                                    if rng.random_bool(0.5) {
+                                        shared_data[idx].store(rng.random(), regime.store());
+                                    } else {
+                                        let _val = shared_data[idx].load(regime.load());
+                                    }
+                                }
+                                AtomicOp::FetchAdd => {
+                                    shared_data[idx].fetch_add(1, regime.rmw());
+                                }
+                                AtomicOp::Swap => {
+                                    shared_data[idx].swap(rng.random(), regime.rmw());
+                                }
+                                AtomicOp::Cas => {
+                                    let cell = &shared_data[idx];
+                                    let mut current = cell.load(regime.load());
+                                    loop {
+                                        match cell.compare_exchange_weak(
+                                            current,
+                                            current.wrapping_add(1),
+                                            regime.rmw(),
+                                            regime.rmw_failure(),
+                                        ) {
+                                            Ok(_) => break,
+                                            Err(observed) => {
+                                                current = observed;
+                                                local_retries += 1;
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+
+                        retries.fetch_add(local_retries, Ordering::Relaxed);
+                    }));
+                }
+
+                for h in handles {
+                    h.join().unwrap();
+                }
+            });
+
+            let ops_per_sec = (num_threads as f64 * iterations_per_combo as f64) / stats.mean.as_secs_f64();
+            println!(
+                "  {:<12} {:<8} {:>16.0} {:>14}",
+                format!("{:?}", op),
+                format!("{:?}", regime),
+                ops_per_sec,
+                retries.load(Ordering::Relaxed)
+            );
+
+            results.push(BenchRecord::new(
+                &format!("atomic_ops/{:?}/{:?}", op, regime),
+                "atomic_ops",
+                num_threads,
+                &stats,
+                Some(ops_per_sec),
+            ));
+
+            first_rate.get_or_insert(ops_per_sec);
+            if op == AtomicOp::StoreLoad && regime == OrderingRegime::Relaxed {
+                baseline_rate = Some(ops_per_sec);
+            }
+        }
+    }
+
+    baseline_rate.or(first_rate).unwrap_or(0.0)
+}
@@ -0,0 +1,148 @@
+//! Structured result collection, machine-readable (JSON/CSV) output, and
+//! `--baseline` regression comparison across runs.
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::stats::SampleStats;
+
+/// Output format for a completed run.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// One benchmark's summary, flattened for serialization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchRecord {
+    pub name: String,
+    pub category: String,
+    pub threads: usize,
+    pub samples: usize,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    pub stddev_ms: f64,
+    pub throughput: Option<f64>,
+}
+
+impl BenchRecord {
+    pub fn new(name: &str, category: &str, threads: usize, stats: &SampleStats, throughput: Option<f64>) -> Self {
+        BenchRecord {
+            name: name.to_string(),
+            category: category.to_string(),
+            threads,
+            samples: stats.kept,
+            mean_ms: stats.mean.as_secs_f64() * 1000.0,
+            median_ms: stats.median.as_secs_f64() * 1000.0,
+            stddev_ms: stats.stddev.as_secs_f64() * 1000.0,
+            throughput,
+        }
+    }
+}
+
+/// Host/run metadata captured once at the start of a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunMetadata {
+    pub cpu_count: usize,
+    pub thread_count: usize,
+    pub hostname: String,
+    pub timestamp_unix: u64,
+}
+
+impl RunMetadata {
+    pub fn capture(thread_count: usize) -> Self {
+        RunMetadata {
+            cpu_count: num_cpus::get(),
+            thread_count,
+            hostname: hostname(),
+            timestamp_unix: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO).as_secs(),
+        }
+    }
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME").unwrap_or_else(|_| {
+        fs::read_to_string("/etc/hostname")
+            .map(|s| s.trim().to_string())
+            .unwrap_or_else(|_| "unknown".to_string())
+    })
+}
+
+/// A full run: metadata plus every benchmark's record, emittable as JSON or CSV.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunResults {
+    pub metadata: RunMetadata,
+    pub records: Vec<BenchRecord>,
+}
+
+impl RunResults {
+    pub fn new(thread_count: usize) -> Self {
+        RunResults { metadata: RunMetadata::capture(thread_count), records: Vec::new() }
+    }
+
+    pub fn push(&mut self, record: BenchRecord) {
+        self.records.push(record);
+    }
+
+    /// Writes the run to `path` in the given format. A no-op for `Text`,
+    /// since text output already streamed to stdout as each section ran.
+    pub fn write(&self, path: &Path, format: OutputFormat) -> std::io::Result<()> {
+        match format {
+            OutputFormat::Text => Ok(()),
+            OutputFormat::Json => fs::write(path, serde_json::to_string_pretty(self).expect("failed to serialize results as JSON")),
+            OutputFormat::Csv => fs::write(path, self.to_csv()),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = String::from("name,category,threads,samples,mean_ms,median_ms,stddev_ms,throughput\n");
+        for r in &self.records {
+            out.push_str(&format!(
+                "{},{},{},{},{:.6},{:.6},{:.6},{}\n",
+                r.name,
+                r.category,
+                r.threads,
+                r.samples,
+                r.mean_ms,
+                r.median_ms,
+                r.stddev_ms,
+                r.throughput.map(|t| t.to_string()).unwrap_or_default(),
+            ));
+        }
+        out
+    }
+
+    /// Loads a previously-written JSON run for `--baseline` comparison.
+    pub fn load_json(path: &Path) -> Self {
+        let text = fs::read_to_string(path).unwrap_or_else(|e| panic!("failed to read baseline {}: {}", path.display(), e));
+        serde_json::from_str(&text).expect("invalid baseline JSON")
+    }
+
+    /// Prints the percentage delta of every matching benchmark's mean against
+    /// `baseline` and returns whether any regressed beyond `tolerance_pct`.
+    pub fn compare_to_baseline(&self, baseline: &RunResults, tolerance_pct: f64) -> bool {
+        println!("\n[Baseline Comparison] (tolerance {:.1}%)", tolerance_pct);
+        let mut regressed = false;
+        for record in &self.records {
+            let Some(prev) = baseline.records.iter().find(|r| r.name == record.name) else {
+                continue;
+            };
+            let delta_pct = (record.mean_ms - prev.mean_ms) / prev.mean_ms * 100.0;
+            let is_regression = delta_pct > tolerance_pct;
+            println!(
+                "  {:<40} {:+.1}%{}",
+                record.name,
+                delta_pct,
+                if is_regression { "  REGRESSION" } else { "" }
+            );
+            regressed |= is_regression;
+        }
+        regressed
+    }
+}
@@ -0,0 +1,103 @@
+//! Normalizes measured throughput against a reference machine profile, the
+//! way validator-hardware benchmarks reduce a run to one comparable number.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Reference throughputs used to normalize a measured run into a per-category
+/// score, where 100 means "as fast as the reference machine".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RefProfile {
+    pub cpu_fib_per_sec: f64,
+    pub mem_bandwidth_gbps: f64,
+    pub mutex_ops_per_sec: f64,
+    pub atomic_ops_per_sec: f64,
+    pub io_seq_write_gbps: f64,
+    pub io_rand_read_gbps: f64,
+    pub fs_ops_per_sec: f64,
+    pub lockfree_ops_per_sec: f64,
+}
+
+impl Default for RefProfile {
+    fn default() -> Self {
+        // Baked-in reference machine, measured once and pinned so scores stay
+        // comparable across benchy runs/releases.
+        RefProfile {
+            cpu_fib_per_sec: 5_000_000.0,
+            mem_bandwidth_gbps: 8.0,
+            mutex_ops_per_sec: 2_000_000.0,
+            atomic_ops_per_sec: 50_000_000.0,
+            io_seq_write_gbps: 0.5,
+            io_rand_read_gbps: 0.2,
+            fs_ops_per_sec: 20_000.0,
+            lockfree_ops_per_sec: 10_000_000.0,
+        }
+    }
+}
+
+impl RefProfile {
+    /// Loads a profile from a TOML or JSON file, picked by file extension.
+    pub fn load(path: &Path) -> Self {
+        let text = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("failed to read reference profile {}: {}", path.display(), e));
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&text).expect("invalid JSON reference profile"),
+            _ => toml::from_str(&text).expect("invalid TOML reference profile"),
+        }
+    }
+}
+
+/// A single category's measured throughput and its score against the reference.
+#[derive(Debug, Clone)]
+pub struct CategoryScore {
+    pub name: &'static str,
+    pub measured: f64,
+    pub reference: f64,
+    pub score: f64,
+}
+
+impl CategoryScore {
+    pub fn new(name: &'static str, measured: f64, reference: f64) -> Self {
+        let score = if reference > 0.0 { measured / reference * 100.0 } else { 0.0 };
+        CategoryScore { name, measured, reference, score }
+    }
+
+    pub fn print(&self) {
+        println!(
+            "  {:<14} measured={:.3} reference={:.3} score={:.1}",
+            self.name, self.measured, self.reference, self.score
+        );
+    }
+}
+
+/// Collects per-category scores for a run and reduces them to an aggregate.
+#[derive(Default)]
+pub struct ScoreReport {
+    pub categories: Vec<CategoryScore>,
+}
+
+impl ScoreReport {
+    pub fn new() -> Self {
+        ScoreReport::default()
+    }
+
+    pub fn push(&mut self, category: CategoryScore) {
+        category.print();
+        self.categories.push(category);
+    }
+
+    pub fn aggregate(&self) -> f64 {
+        if self.categories.is_empty() {
+            return 0.0;
+        }
+        self.categories.iter().map(|c| c.score).sum::<f64>() / self.categories.len() as f64
+    }
+
+    /// Categories that scored below `min_score`.
+    pub fn below_threshold(&self, min_score: f64) -> Vec<&CategoryScore> {
+        self.categories.iter().filter(|c| c.score < min_score).collect()
+    }
+}
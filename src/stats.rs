@@ -0,0 +1,127 @@
+//! Statistical measurement core shared by every benchmark section.
+//!
+//! [`measure`] runs a closure through a warmup phase, collects repeated
+//! timing samples, rejects Tukey outliers, and reduces what's left to
+//! [`SampleStats`] (mean/median/stddev/min/p95/p99) instead of the single
+//! `Instant::now()` reading the benchmarks used to report.
+
+use std::time::{Duration, Instant};
+
+/// Summary statistics for a batch of timing samples, after outlier rejection.
+#[derive(Debug, Clone)]
+pub struct SampleStats {
+    pub mean: Duration,
+    pub median: Duration,
+    pub stddev: Duration,
+    pub min: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub kept: usize,
+    pub dropped: usize,
+}
+
+impl SampleStats {
+    /// Sorts `samples`, discards Tukey outliers (outside
+    /// `[Q1 - 1.5*IQR, Q3 + 1.5*IQR]`), and summarizes what remains.
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+        let total = samples.len();
+
+        let q1 = percentile(&samples, 0.25);
+        let q3 = percentile(&samples, 0.75);
+        let iqr = q3.saturating_sub(q1);
+        let fence = scale(iqr, 1.5);
+        let low = q1.saturating_sub(fence);
+        let high = q3.saturating_add(fence);
+
+        let kept: Vec<Duration> = samples.iter().copied().filter(|d| *d >= low && *d <= high).collect();
+        let dropped = total - kept.len();
+
+        let mean = mean_of(&kept);
+        let stddev = stddev_of(&kept, mean);
+
+        SampleStats {
+            mean,
+            median: percentile(&kept, 0.5),
+            stddev,
+            min: *kept.first().unwrap_or(&Duration::ZERO),
+            p95: percentile(&kept, 0.95),
+            p99: percentile(&kept, 0.99),
+            kept: kept.len(),
+            dropped,
+        }
+    }
+
+    pub fn print(&self, label: &str) {
+        println!(
+            "  {}: mean={:?} median={:?} stddev={:?} min={:?} p95={:?} p99={:?} (kept {}, dropped {})",
+            label, self.mean, self.median, self.stddev, self.min, self.p95, self.p99,
+            self.kept, self.dropped
+        );
+    }
+}
+
+fn scale(d: Duration, factor: f64) -> Duration {
+    Duration::from_secs_f64(d.as_secs_f64() * factor)
+}
+
+/// Linear-interpolated percentile over an already-sorted slice.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = p * (sorted.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    if lo == hi {
+        sorted[lo]
+    } else {
+        sorted[lo] + scale(sorted[hi] - sorted[lo], rank - lo as f64)
+    }
+}
+
+fn mean_of(samples: &[Duration]) -> Duration {
+    if samples.is_empty() {
+        return Duration::ZERO;
+    }
+    samples.iter().sum::<Duration>() / samples.len() as u32
+}
+
+fn stddev_of(samples: &[Duration], mean: Duration) -> Duration {
+    if samples.len() < 2 {
+        return Duration::ZERO;
+    }
+    let mean_s = mean.as_secs_f64();
+    let variance = samples
+        .iter()
+        .map(|d| {
+            let diff = d.as_secs_f64() - mean_s;
+            diff * diff
+        })
+        .sum::<f64>()
+        / (samples.len() - 1) as f64;
+    Duration::from_secs_f64(variance.sqrt())
+}
+
+/// Runs `f` for `warmup` untimed iterations, then collects timed samples
+/// until both `num_samples` have been gathered and `min_time` has elapsed,
+/// prints the resulting [`SampleStats`] under `name`, and returns it.
+pub fn measure<F: FnMut()>(name: &str, warmup: u32, num_samples: u32, min_time: Duration, mut f: F) -> SampleStats {
+    assert!(num_samples >= 1, "measure: num_samples must be at least 1, got 0");
+
+    for _ in 0..warmup {
+        f();
+    }
+
+    let mut raw = Vec::with_capacity(num_samples as usize);
+    let measure_start = Instant::now();
+    while raw.len() < num_samples as usize || measure_start.elapsed() < min_time {
+        let start = Instant::now();
+        f();
+        raw.push(start.elapsed());
+    }
+
+    let stats = SampleStats::from_samples(raw);
+    stats.print(name);
+    stats
+}